@@ -1,12 +1,22 @@
 use libember_sys::{pcstr, size_t};
 use std::{
+    alloc::{self, Layout},
     ffi::{c_void, CStr},
+    mem,
     net::SocketAddr,
     os::raw::c_int,
-    thread,
+    ptr, thread,
     time::Duration,
 };
 
+// libember only hands the pointer back to free_memory, so the size of each
+// allocation is stored in a header in front of the block returned to C.
+// The header is as large as the alignment to keep the payload aligned like
+// malloc would.
+const ALLOC_ALIGN: usize = 16;
+const ALLOC_HEADER_LEN: usize = ALLOC_ALIGN;
+const _: () = assert!(ALLOC_HEADER_LEN >= mem::size_of::<usize>());
+
 pub fn connect(addr: SocketAddr) {
     log::debug!("Using socket address {:?}", addr);
 
@@ -34,10 +44,89 @@ unsafe extern "C" fn fail_assertion(p_file_name: pcstr, line_number: c_int) {
     log::error!("Debug assertion failed @ '{}' line {}", file, line_number);
 }
 
-unsafe extern "C" fn alloc_memory(_size: size_t) -> *mut c_void {
-    todo!()
+unsafe extern "C" fn alloc_memory(size: size_t) -> *mut c_void {
+    // size_t is not an alias of usize on every target
+    #[allow(clippy::unnecessary_cast)]
+    let size = size as usize;
+
+    let layout = match alloc_layout(size) {
+        Some(layout) => layout,
+        None => {
+            log::error!("Invalid allocation size requested: {}", size);
+            return ptr::null_mut();
+        }
+    };
+
+    let p_block = alloc::alloc(layout);
+    if p_block.is_null() {
+        log::error!("Failed to allocate {} bytes", size);
+        return ptr::null_mut();
+    }
+
+    (p_block as *mut usize).write(size);
+    p_block.add(ALLOC_HEADER_LEN) as *mut c_void
+}
+
+unsafe extern "C" fn free_memory(p_memory: *mut c_void) {
+    if p_memory.is_null() {
+        return;
+    }
+
+    let p_block = (p_memory as *mut u8).sub(ALLOC_HEADER_LEN);
+    let size = (p_block as *const usize).read();
+
+    match alloc_layout(size) {
+        Some(layout) => alloc::dealloc(p_block, layout),
+        None => log::error!("Corrupted allocation header at {:?}", p_memory),
+    }
 }
 
-unsafe extern "C" fn free_memory(_p_memory: *mut c_void) {
-    todo!()
+fn alloc_layout(size: usize) -> Option<Layout> {
+    let total = size.checked_add(ALLOC_HEADER_LEN)?;
+    Layout::from_size_align(total, ALLOC_ALIGN).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ember_init_completes() {
+        unsafe {
+            libember_sys::ember_init(
+                Some(throw_error),
+                Some(fail_assertion),
+                Some(alloc_memory),
+                Some(free_memory),
+            );
+        }
+    }
+
+    #[test]
+    fn alloc_and_free_round_trip() {
+        for size in [0, 1, 4096] {
+            unsafe {
+                let p_memory = alloc_memory(size);
+                assert!(!p_memory.is_null());
+                assert_eq!(p_memory as usize % ALLOC_ALIGN, 0);
+                #[allow(clippy::unnecessary_cast)]
+                ptr::write_bytes(p_memory as *mut u8, 0xab, size as usize);
+                free_memory(p_memory);
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_alloc_returns_null() {
+        unsafe {
+            assert!(alloc_memory(size_t::MAX).is_null());
+        }
+    }
+
+    #[test]
+    fn free_null_is_noop() {
+        unsafe {
+            free_memory(ptr::null_mut());
+        }
+    }
 }